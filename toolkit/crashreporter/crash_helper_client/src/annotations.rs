@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Crash annotations: free-form key/value pairs (product/version/channel,
+//! active feature flags, ...) that the embedding application wants
+//! persisted alongside a minidump.
+//!
+//! These are handed to the helper at spawn time, rather than at the moment
+//! of the crash, because the crashing process may not be able to run any
+//! code (including IPC calls) by the time the helper actually needs them.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::ffi::CString;
+
+/// A set of crash annotations, keyed by name.
+#[derive(Debug, Default, Clone)]
+pub struct CrashAnnotations(HashMap<String, String>);
+
+impl CrashAnnotations {
+    /// Creates an empty set of annotations.
+    pub fn new() -> CrashAnnotations {
+        CrashAnnotations(HashMap::new())
+    }
+
+    /// Records an annotation, overwriting any previous value for `key`.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(key.into(), value.into());
+    }
+
+    /// Serializes the annotations into a single nul-terminated argument
+    /// suitable for passing to `posix_spawn`.
+    ///
+    /// Annotations are free-form (feature flags, command-line arguments,
+    /// ...) and may themselves contain `=` or any other separator we might
+    /// otherwise pick, so each key and value is length-prefixed (`len:data`)
+    /// rather than joined with a delimiter. The helper parses its argv the
+    /// same way.
+    pub(crate) fn serialize(&self) -> Result<CString> {
+        let mut encoded = String::new();
+        for (key, value) in &self.0 {
+            encoded.push_str(&format!("{}:{key}", key.len()));
+            encoded.push_str(&format!("{}:{value}", value.len()));
+        }
+
+        match CString::new(encoded) {
+            Ok(encoded) => Ok(encoded),
+            Err(err) => bail!("crash annotation contains an embedded NUL byte: {err}"),
+        }
+    }
+}
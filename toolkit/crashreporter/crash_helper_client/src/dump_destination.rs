@@ -0,0 +1,38 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Where the helper should write this client's minidump, as seen from the
+//! client side of the registration handshake. Mirrors the server's
+//! `DumpDestination`, but only the templated variant needs a client-facing
+//! type: the fixed case is already fully described by `minidump_path`.
+
+use std::path::PathBuf;
+
+/// Asks the helper to derive a self-describing filename for this client's
+/// minidump instead of writing to a caller-fixed path; see the server's
+/// `DumpDestination::Templated`.
+#[derive(Debug, Clone)]
+pub struct DumpDestinationTemplate {
+    pub directory: PathBuf,
+    pub product_name: String,
+    pub product_version: String,
+}
+
+impl DumpDestinationTemplate {
+    pub fn new(
+        directory: impl Into<PathBuf>,
+        product_name: impl Into<String>,
+        product_version: impl Into<String>,
+    ) -> DumpDestinationTemplate {
+        DumpDestinationTemplate {
+            directory: directory.into(),
+            product_name: product_name.into(),
+            product_version: product_version.into(),
+        }
+    }
+
+    pub(crate) fn into_registration_arg(self) -> (PathBuf, String, String) {
+        (self.directory, self.product_name, self.product_version)
+    }
+}
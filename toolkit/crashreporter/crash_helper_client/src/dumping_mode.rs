@@ -0,0 +1,30 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Selects how the spawned helper generates minidumps.
+
+use std::ffi::CString;
+
+/// How the helper process should generate minidumps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpingMode {
+    /// Delegate to the legacy, external C++ breakpad helper binary.
+    Legacy,
+    /// Use the in-crate `minidump-writer` based native implementation
+    /// (Linux only, for now).
+    Native,
+}
+
+impl DumpingMode {
+    /// Encodes the mode as an extra `posix_spawn` argument for the helper
+    /// to parse out of its argv.
+    pub(crate) fn serialize(&self) -> CString {
+        let value = match self {
+            DumpingMode::Legacy => "legacy",
+            DumpingMode::Native => "native",
+        };
+
+        CString::new(value).unwrap()
+    }
+}
@@ -0,0 +1,134 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Filtering of the environment variables forwarded to the crash helper
+//! process.
+//!
+//! The helper is a long-lived process that may outlive the one that spawned
+//! it and can end up embedding parts of its environment in logs or crash
+//! annotations, so we don't want to hand it the parent's environment
+//! verbatim. The helper only needs a small, well-known set of variables
+//! (locale, temp directory, a few `MOZ_`-prefixed ones) to do its job.
+
+use std::env;
+use std::ffi::CString;
+
+/// Variable names that are forwarded to the helper by default.
+const DEFAULT_ALLOWED_NAMES: &[&str] = &["TMPDIR", "TMP", "TEMP", "LANG"];
+
+/// Variable name prefixes that are forwarded to the helper by default.
+const DEFAULT_ALLOWED_PREFIXES: &[&str] = &["LC_", "MOZ_"];
+
+/// Name prefixes that are never forwarded to the helper, regardless of the
+/// allowlist in effect. This is the "boring switch": anything that looks
+/// like it could carry a credential is dropped unconditionally.
+const DENIED_PREFIXES: &[&str] = &[
+    "AWS_", "AZURE_", "GOOGLE_", "GITHUB_", "GCP_", "NPM_", "DOCKER_",
+];
+
+/// Exact variable names that are never forwarded, for the same reason.
+const DENIED_NAMES: &[&str] = &[
+    "TOKEN",
+    "API_KEY",
+    "SECRET",
+    "PASSWORD",
+    "SESSION_ID",
+    "AUTH_TOKEN",
+    "SSH_AUTH_SOCK",
+    "GPG_AGENT_INFO",
+];
+
+fn is_denied(name: &str) -> bool {
+    DENIED_NAMES.contains(&name) || DENIED_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
+fn is_default_allowed(name: &str) -> bool {
+    DEFAULT_ALLOWED_NAMES.contains(&name)
+        || DEFAULT_ALLOWED_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// Collects the environment variables that should be forwarded to the crash
+/// helper, as a list of `NAME=value` C strings ready to be passed to
+/// `posix_spawn`.
+///
+/// When `allowlist` is `None` the built-in conservative default is used.
+/// Tests (or other trusted callers) can supply an explicit `allowlist` to
+/// override it; the denylist still applies on top of it so that an
+/// overly-broad allowlist can never leak a known-sensitive variable.
+pub(crate) fn filtered_env(allowlist: Option<&[&str]>) -> Vec<CString> {
+    filter_vars(env::vars(), allowlist)
+}
+
+/// The filtering logic proper, decoupled from `env::vars()` so it can be
+/// exercised against fixed input in tests.
+fn filter_vars(
+    vars: impl Iterator<Item = (String, String)>,
+    allowlist: Option<&[&str]>,
+) -> Vec<CString> {
+    vars.filter(|(name, _)| {
+        if is_denied(name) {
+            return false;
+        }
+
+        match allowlist {
+            Some(names) => names.contains(&name.as_str()),
+            None => is_default_allowed(name),
+        }
+    })
+    .map(|(name, value)| format!("{name}={value}"))
+    .map(|string| CString::new(string).unwrap())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names_of(env: &[CString]) -> Vec<String> {
+        env.iter()
+            .map(|entry| entry.to_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn default_allowlist_drops_sensitive_vars() {
+        let vars = vec![
+            ("AUTH_TOKEN".to_owned(), "secret".to_owned()),
+            ("AWS_SECRET_ACCESS_KEY".to_owned(), "leak".to_owned()),
+            ("LANG".to_owned(), "en_US.UTF-8".to_owned()),
+            ("MOZ_CRASHREPORTER".to_owned(), "1".to_owned()),
+            ("RANDOM_VAR".to_owned(), "nope".to_owned()),
+        ];
+
+        let names = names_of(&filter_vars(vars.into_iter(), None));
+
+        assert!(names.iter().any(|entry| entry == "LANG=en_US.UTF-8"));
+        assert!(names.iter().any(|entry| entry == "MOZ_CRASHREPORTER=1"));
+        assert!(!names.iter().any(|entry| entry.starts_with("AUTH_TOKEN")));
+        assert!(!names
+            .iter()
+            .any(|entry| entry.starts_with("AWS_SECRET_ACCESS_KEY")));
+        assert!(!names.iter().any(|entry| entry.starts_with("RANDOM_VAR")));
+    }
+
+    #[test]
+    fn caller_allowlist_is_still_subject_to_the_denylist() {
+        let vars = vec![
+            ("AWS_SECRET_ACCESS_KEY".to_owned(), "leak".to_owned()),
+            ("LANG".to_owned(), "en_US.UTF-8".to_owned()),
+        ];
+
+        let names = names_of(&filter_vars(
+            vars.into_iter(),
+            Some(&["AWS_SECRET_ACCESS_KEY", "LANG"]),
+        ));
+
+        assert!(names.iter().any(|entry| entry == "LANG=en_US.UTF-8"));
+        assert!(!names
+            .iter()
+            .any(|entry| entry.starts_with("AWS_SECRET_ACCESS_KEY")));
+    }
+}
@@ -0,0 +1,33 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Client-side API used by the crashing process to spawn and talk to the
+//! out-of-process crash helper.
+
+mod annotations;
+mod dump_destination;
+mod dumping_mode;
+mod env;
+
+#[cfg_attr(unix, path = "platform/unix.rs")]
+#[cfg_attr(windows, path = "platform/windows.rs")]
+mod platform;
+
+use crash_helper_common::IPCConnector;
+
+pub use annotations::CrashAnnotations;
+pub use dump_destination::DumpDestinationTemplate;
+pub use dumping_mode::DumpingMode;
+
+/// A connection to the out-of-process crash helper, used to request that
+/// minidumps be generated when this process (or one of its children)
+/// crashes.
+pub struct CrashHelperClient {
+    connector: IPCConnector,
+    spawner_thread: Option<std::thread::JoinHandle<()>>,
+    /// Pid of the spawned helper process, used on platforms where the
+    /// crashing process must explicitly authorize the helper to inspect it
+    /// (see `prepare_for_minidump`).
+    helper_pid: crash_helper_common::Pid,
+}
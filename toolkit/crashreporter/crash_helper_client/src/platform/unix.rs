@@ -3,27 +3,38 @@
  * You can obtain one at http://mozilla.org/MPL/2.0/. */
 
 use anyhow::{bail, Result};
-use crash_helper_common::{ignore_eintr, BreakpadChar, BreakpadData, IPCChannel, IPCConnector};
+use crash_helper_common::{
+    ignore_eintr, BreakpadChar, BreakpadData, IPCChannel, IPCConnector, IPCListener,
+};
 use nix::{
     spawn::{posix_spawn, PosixSpawnAttr, PosixSpawnFileActions},
     sys::wait::{waitpid, WaitStatus},
     unistd::getpid,
 };
-use std::{
-    env,
-    ffi::{CStr, CString},
-};
+use std::ffi::{CStr, CString};
+use std::path::PathBuf;
 
-use crate::CrashHelperClient;
+use crate::{
+    env::filtered_env, CrashAnnotations, CrashHelperClient, DumpDestinationTemplate, DumpingMode,
+};
 
 impl CrashHelperClient {
     pub(crate) fn new(
         program: *const BreakpadChar,
         breakpad_data: BreakpadData,
         minidump_path: *const BreakpadChar,
+        annotations: CrashAnnotations,
+        dumping_mode: DumpingMode,
+        destination_template: Option<DumpDestinationTemplate>,
     ) -> Result<CrashHelperClient> {
         let channel = IPCChannel::new()?;
-        let (_listener, server_endpoint, client_endpoint) = channel.deconstruct();
+        // `server_endpoint` and `client_endpoint` are a pre-connected pair
+        // that the `CrashGenerationServer`'s `listener.accept()` loop never
+        // sees, so they can't be used to register with it; only `listener`
+        // (handed to the helper below) is. `server_endpoint` is still
+        // passed along to the helper for its own bootstrapping, but we
+        // don't register over `client_endpoint` ourselves.
+        let (listener, server_endpoint, _client_endpoint) = channel.deconstruct();
         // SAFETY: `program` is guaranteed to point to a valid nul-terminated
         // string by the caller.
         let program = unsafe { CStr::from_ptr(program) };
@@ -31,40 +42,101 @@ impl CrashHelperClient {
         // nul-terminated string by the caller.
         let breakpad_data =
             unsafe { CString::from_vec_unchecked(breakpad_data.to_string().into_bytes()) };
-        // SAFETY: `minidump_path` is guaranteed to point to a valid
-        // nul-terminated string by the caller.
-        let minidump_path = unsafe { CStr::from_ptr(minidump_path) };
 
-        CrashHelperClient::spawn_crash_helper(
+        let (helper_pid, listener_address) = CrashHelperClient::spawn_crash_helper(
             program,
             breakpad_data,
-            minidump_path,
+            // SAFETY: `minidump_path` is guaranteed to point to a valid
+            // nul-terminated string by the caller.
+            unsafe { CStr::from_ptr(minidump_path) },
+            annotations,
+            dumping_mode,
+            listener,
             server_endpoint,
         )?;
 
+        // Join the freshly spawned helper's `CrashGenerationServer` the
+        // same way any later client in this browser instance would, via
+        // its listener address, rather than the pre-connected pair above.
+        CrashHelperClient::connect(
+            &listener_address,
+            minidump_path,
+            helper_pid,
+            destination_template,
+        )
+    }
+
+    /// Joins an already-running helper's `CrashGenerationServer` rather
+    /// than spawning a new helper process, for additional client processes
+    /// in the same browser instance. `server_address` is the serialized
+    /// listener address that `spawn_crash_helper` hands to the helper;
+    /// `CrashHelperClient` itself does not store or expose it.
+    ///
+    /// `destination_template` asks the helper to derive a self-describing
+    /// filename for this client's dump instead of writing to
+    /// `minidump_path`; see `DumpDestinationTemplate`.
+    pub(crate) fn connect(
+        server_address: &CStr,
+        minidump_path: *const BreakpadChar,
+        helper_pid: crash_helper_common::Pid,
+        destination_template: Option<DumpDestinationTemplate>,
+    ) -> Result<CrashHelperClient> {
+        let connector = IPCConnector::connect(server_address)?;
+        // SAFETY: `minidump_path` is guaranteed to point to a valid
+        // nul-terminated string by the caller.
+        let minidump_path = unsafe { CStr::from_ptr(minidump_path) };
+        let minidump_path_buf = PathBuf::from(minidump_path.to_string_lossy().into_owned());
+
+        connector.send_registration(
+            getpid(),
+            minidump_path_buf,
+            destination_template.map(DumpDestinationTemplate::into_registration_arg),
+        )?;
+
         Ok(CrashHelperClient {
-            connector: client_endpoint,
+            connector,
             spawner_thread: None,
+            helper_pid,
         })
     }
 
+    /// Asks the helper to write a minidump for this process, blocking until
+    /// it confirms that the dump has been written, or returning an error if
+    /// the helper reports that it failed.
+    pub(crate) fn request_dump(&self) -> Result<()> {
+        // The helper writes the dump by ptracing us, so it must be
+        // authorized as our tracer before we ask it to; see
+        // `prepare_for_minidump`.
+        if !Self::prepare_for_minidump(self.helper_pid) {
+            bail!("failed to authorize the crash helper to trace this process");
+        }
+
+        self.connector.send_dump_request()?;
+        self.connector.recv_dump_complete()
+    }
+
     fn spawn_crash_helper(
         program: &CStr,
         breakpad_data: CString,
         minidump_path: &CStr,
+        annotations: CrashAnnotations,
+        dumping_mode: DumpingMode,
+        listener: IPCListener,
         server_endpoint: IPCConnector,
-    ) -> Result<()> {
+    ) -> Result<(crash_helper_common::Pid, CString)> {
         let parent_pid = getpid().to_string();
         let parent_pid_arg = unsafe { CString::from_vec_unchecked(parent_pid.into_bytes()) };
+        let listener_arg = listener.serialize()?;
         let endpoint_arg = server_endpoint.serialize()?;
+        let annotations_arg = annotations.serialize()?;
+        let dumping_mode_arg = dumping_mode.serialize();
 
         let file_actions = PosixSpawnFileActions::init()?;
         let attr = PosixSpawnAttr::init()?;
 
-        let env: Vec<CString> = env::vars()
-            .map(|(key, value)| format!("{key}={value}"))
-            .map(|string| CString::new(string).unwrap())
-            .collect();
+        // Only a conservative allowlist of variables is forwarded to the
+        // helper; see `crate::env` for the rationale.
+        let env = filtered_env(None);
 
         let pid = posix_spawn(
             program,
@@ -75,7 +147,10 @@ impl CrashHelperClient {
                 &parent_pid_arg,
                 &breakpad_data,
                 minidump_path,
+                &listener_arg,
                 &endpoint_arg,
+                &annotations_arg,
+                &dumping_mode_arg,
             ],
             env.as_slice(),
         )?;
@@ -85,15 +160,51 @@ impl CrashHelperClient {
         let status = ignore_eintr!(waitpid(pid, None))?;
 
         if let WaitStatus::Exited(_, _) = status {
-            Ok(())
+            Ok((pid, listener_arg))
         } else {
             bail!("The crash helper process failed to start and exited with status: {status:?}");
         }
     }
 
+    /// Returns the pid of the spawned helper process, captured from the
+    /// `posix_spawn` call in `spawn_crash_helper`.
+    pub(crate) fn helper_pid(&self) -> crash_helper_common::Pid {
+        self.helper_pid
+    }
+
+    #[cfg(target_os = "linux")]
+    pub(crate) fn prepare_for_minidump(helper_pid: crash_helper_common::Pid) -> bool {
+        use nix::{errno::Errno, libc::PR_SET_PTRACER};
+
+        // The helper writes minidumps by ptracing the crashing process, so
+        // on systems enforcing Yama's ptrace_scope restriction we must
+        // explicitly authorize it as our tracer before asking it to dump
+        // us.
+        match Errno::result(unsafe {
+            nix::libc::prctl(PR_SET_PTRACER, helper_pid.as_raw(), 0, 0, 0)
+        }) {
+            Ok(_) => true,
+            // EINVAL means the running kernel doesn't implement Yama, in
+            // which case tracing is already allowed.
+            Err(Errno::EINVAL) => true,
+            Err(_) => false,
+        }
+    }
+
     #[cfg(not(target_os = "linux"))]
     pub(crate) fn prepare_for_minidump(_pid: crash_helper_common::Pid) -> bool {
         // This is a no-op on platforms that don't need it
         true
     }
 }
+
+#[cfg(target_os = "linux")]
+impl Drop for CrashHelperClient {
+    fn drop(&mut self) {
+        // Revoke the tracer authorization granted in `prepare_for_minidump`
+        // now that we're disconnecting from the helper.
+        let _ = nix::errno::Errno::result(unsafe {
+            nix::libc::prctl(nix::libc::PR_SET_PTRACER, 0, 0, 0, 0)
+        });
+    }
+}
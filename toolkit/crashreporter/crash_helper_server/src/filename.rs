@@ -0,0 +1,49 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Composing self-describing minidump filenames.
+//!
+//! A directory full of dumps collected from mixed builds is much easier to
+//! triage when the product version and crash time are encoded in the file
+//! name itself, rather than every dump sharing a caller-fixed path. This
+//! mirrors what the Mac crash-generation server does.
+
+use std::path::PathBuf;
+
+/// Where a client's minidump should be written.
+#[derive(Debug, Clone)]
+pub(crate) enum DumpDestination {
+    /// Write to this exact, caller-supplied path. Kept for back-compat with
+    /// callers that don't use the templated naming scheme.
+    Fixed(PathBuf),
+    /// Derive a self-describing filename inside `directory`, of the form
+    /// `{product_name}-{product_version}-{YYYYMMDDHHMMSS}-{uuid}.dmp`.
+    Templated {
+        directory: PathBuf,
+        product_name: String,
+        product_version: String,
+    },
+}
+
+impl DumpDestination {
+    /// Resolves the final path to write the minidump to. For the templated
+    /// variant this is computed fresh every time, so the timestamp reflects
+    /// the moment of the crash rather than client registration.
+    pub(crate) fn resolve(&self) -> PathBuf {
+        match self {
+            DumpDestination::Fixed(path) => path.clone(),
+            DumpDestination::Templated {
+                directory,
+                product_name,
+                product_version,
+            } => {
+                let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S");
+                let uuid = uuid::Uuid::new_v4();
+                let filename = format!("{product_name}-{product_version}-{timestamp}-{uuid}.dmp");
+
+                directory.join(filename)
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! The out-of-process side of the crash helper: a single listening
+//! endpoint shared by every client process (rather than one helper per
+//! client), serving minidump-generation requests as they come in.
+
+mod filename;
+mod minidump_writer;
+
+#[cfg_attr(unix, path = "platform/unix.rs")]
+#[cfg_attr(windows, path = "platform/windows.rs")]
+mod platform;
+
+use crash_helper_common::{IPCConnector, Pid};
+
+pub(crate) use filename::DumpDestination;
+pub use minidump_writer::MinidumpWriter;
+
+/// Everything the server tracks about a single connected client.
+pub(crate) struct ClientInfo {
+    pub(crate) pid: Pid,
+    pub(crate) connector: IPCConnector,
+    /// Where to write this client's minidump, received at registration.
+    pub(crate) destination: DumpDestination,
+}
+
+/// Accepts connections from arbitrarily many client processes and
+/// dispatches minidump-generation requests for them, from a dedicated
+/// background thread.
+///
+/// This is the out-of-process model Breakpad/Chrome use on Linux: the
+/// helper owns one listening endpoint plus a thread that polls it
+/// alongside a control pipe, so a single helper process can serve a whole
+/// process tree instead of spawning one helper per client.
+pub struct CrashGenerationServer {
+    thread: Option<std::thread::JoinHandle<()>>,
+    shutdown: platform::ShutdownHandle,
+}
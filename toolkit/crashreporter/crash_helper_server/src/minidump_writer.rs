@@ -0,0 +1,46 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+//! Native (Rust) minidump generation, used instead of delegating to an
+//! external C++ breakpad helper binary.
+//!
+//! Once the target process has authorized us to trace it (see
+//! `CrashHelperClient::prepare_for_minidump` in `crash_helper_client`), we
+//! can open `/proc/<pid>`,
+//! stop its threads and write the minidump ourselves, without spawning a
+//! separate process to do it.
+
+use anyhow::Result;
+use crash_helper_common::Pid;
+use minidump_writer::minidump_writer::MinidumpWriter as NativeWriter;
+use std::{fs, fs::File, path::Path};
+
+/// Writes minidumps for a ptraced process using the in-crate Rust
+/// `minidump-writer` implementation.
+pub struct MinidumpWriter;
+
+impl MinidumpWriter {
+    /// Stops `pid` and writes a minidump describing it to `path`.
+    ///
+    /// The dump is first written to a sibling temporary file and only
+    /// renamed into place once it's complete, so that a failed dump never
+    /// leaves a zero-length or partial file at `path`.
+    pub fn write(pid: Pid, path: &Path) -> Result<()> {
+        let temp_path = path.with_extension("tmp");
+        let mut dump_file = File::create(&temp_path)?;
+
+        // `minidump-writer` takes raw pids, not the `nix::unistd::Pid`
+        // newtype.
+        let result = NativeWriter::new(pid.as_raw(), pid.as_raw()).dump(&mut dump_file);
+        drop(dump_file);
+
+        if let Err(err) = result {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err.into());
+        }
+
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this file,
+ * You can obtain one at http://mozilla.org/MPL/2.0/. */
+
+use anyhow::Result;
+use crash_helper_common::{ignore_eintr, IPCConnector, IPCListener};
+use nix::{
+    poll::{poll, PollFd, PollFlags, PollTimeout},
+    unistd::{pipe, write},
+};
+use std::os::fd::{AsFd, OwnedFd};
+
+use crate::{ClientInfo, CrashGenerationServer, DumpDestination, MinidumpWriter};
+
+/// The write end of the control pipe used to wake the poll loop up for
+/// shutdown. A single byte written to it tells the loop to stop.
+pub(crate) struct ShutdownHandle(OwnedFd);
+
+impl ShutdownHandle {
+    fn signal(&self) {
+        // The poll loop only cares that the pipe became readable, the byte
+        // value itself is irrelevant.
+        let _ = write(&self.0, &[0u8]);
+    }
+}
+
+impl CrashGenerationServer {
+    /// Starts the server: spawns the background thread that accepts
+    /// connections on `listener` and serves minidump requests for however
+    /// many clients connect to it.
+    pub fn new(listener: IPCListener) -> Result<CrashGenerationServer> {
+        let (shutdown_read, shutdown_write) = pipe()?;
+
+        let thread = std::thread::Builder::new()
+            .name("CrashGenerationServer".to_owned())
+            .spawn(move || poll_loop(listener, shutdown_read))?;
+
+        Ok(CrashGenerationServer {
+            thread: Some(thread),
+            shutdown: ShutdownHandle(shutdown_write),
+        })
+    }
+
+    /// Signals the background thread to stop and waits for it to exit.
+    pub fn shutdown(&mut self) {
+        self.shutdown.signal();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for CrashGenerationServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn poll_loop(listener: IPCListener, shutdown_read: OwnedFd) {
+    let mut clients: Vec<ClientInfo> = Vec::new();
+
+    loop {
+        let mut fds: Vec<PollFd> = Vec::with_capacity(clients.len() + 2);
+        fds.push(PollFd::new(listener.as_fd(), PollFlags::POLLIN));
+        fds.push(PollFd::new(shutdown_read.as_fd(), PollFlags::POLLIN));
+        for client in &clients {
+            fds.push(PollFd::new(client.connector.as_fd(), PollFlags::POLLIN));
+        }
+
+        if ignore_eintr!(poll(&mut fds, PollTimeout::NONE)).is_err() {
+            return;
+        }
+
+        // A readable control pipe means it's time to shut down.
+        if fds[1]
+            .revents()
+            .is_some_and(|revents| revents.contains(PollFlags::POLLIN))
+        {
+            return;
+        }
+
+        if fds[0]
+            .revents()
+            .is_some_and(|revents| revents.contains(PollFlags::POLLIN))
+        {
+            if let Ok(connector) = listener.accept() {
+                // `fixed_path` and a templated destination's `directory`
+                // are registered as distinct fields on the wire, not one
+                // value doing double duty depending on `template`.
+                if let Ok((pid, fixed_path, template)) = connector.recv_registration() {
+                    let destination = match template {
+                        Some((directory, product_name, product_version)) => {
+                            DumpDestination::Templated {
+                                directory,
+                                product_name,
+                                product_version,
+                            }
+                        }
+                        None => DumpDestination::Fixed(fixed_path),
+                    };
+
+                    clients.push(ClientInfo {
+                        pid,
+                        connector,
+                        destination,
+                    });
+                }
+            }
+        }
+
+        for (client, poll_fd) in clients.iter_mut().zip(fds[2..].iter()) {
+            let Some(revents) = poll_fd.revents() else {
+                continue;
+            };
+
+            if revents.contains(PollFlags::POLLIN) {
+                let path = client.destination.resolve();
+
+                // The client blocks on `recv_dump_complete` after sending a
+                // dump request, so it must always get a reply, success or
+                // not; silently doing nothing on failure would leave it
+                // hanging forever.
+                match MinidumpWriter::write(client.pid, &path) {
+                    Ok(()) => {
+                        let _ = client.connector.send_dump_complete();
+                    }
+                    Err(_) => {
+                        let _ = client.connector.send_dump_failed();
+                    }
+                }
+            }
+        }
+
+        clients.retain(|client| !client.connector.is_closed());
+    }
+}